@@ -17,13 +17,14 @@
 
 use crate::utils::{make_scalar_function, utf8_to_int_type};
 use arrow::array::{
-    Array, ArrayRef, ArrowPrimitiveType, AsArray, OffsetSizeTrait, PrimitiveArray,
-    StringArrayType,
+    Array, ArrayRef, ArrowPrimitiveType, AsArray, GenericBinaryArray, GenericStringArray,
+    OffsetSizeTrait, PrimitiveArray, StringArrayType,
 };
+use arrow::buffer::NullBuffer;
 use arrow::datatypes::{ArrowNativeType, DataType, Int32Type, Int64Type};
-use datafusion_common::Result;
+use datafusion_common::{exec_err, plan_err, Result};
 use datafusion_expr::{
-    ColumnarValue, Documentation, ScalarUDFImpl, Signature, Volatility,
+    ColumnarValue, Documentation, ScalarUDFImpl, Signature, TypeSignature, Volatility,
 };
 use datafusion_macros::user_doc;
 use std::any::Any;
@@ -31,7 +32,7 @@ use std::sync::Arc;
 
 #[user_doc(
     doc_section(label = "String Functions"),
-    description = "Returns the number of characters in a string.",
+    description = "Returns the number of characters in a string. When called with a second `encoding` argument, the first argument is treated as raw bytes stored in that server encoding (`UTF8`, `LATIN1` or `WIN1252`) and decoded before counting, mirroring PostgreSQL's two-argument `length`.",
     syntax_example = "character_length(str)",
     sql_example = r#"```sql
 > select character_length('Ångström');
@@ -40,6 +41,15 @@ use std::sync::Arc;
 +------------------------------------+
 | 8                                  |
 +------------------------------------+
+```
+
+```sql
+> select character_length(X'E5746FF16F73', 'LATIN1');
++---------------------------------------------------------------+
+| character_length(Binary("E5746FF16F73"),Utf8("LATIN1")) |
++---------------------------------------------------------------+
+| 6                                                               |
++---------------------------------------------------------------+
 ```"#,
     standard_argument(name = "str", prefix = "String"),
     related_udf(name = "bit_length"),
@@ -61,9 +71,12 @@ impl CharacterLengthFunc {
     pub fn new() -> Self {
         use DataType::*;
         Self {
-            signature: Signature::uniform(
-                1,
-                vec![Utf8, LargeUtf8, Utf8View],
+            signature: Signature::one_of(
+                vec![
+                    TypeSignature::Uniform(1, vec![Utf8, LargeUtf8, Utf8View]),
+                    TypeSignature::Exact(vec![Binary, Utf8]),
+                    TypeSignature::Exact(vec![LargeBinary, Utf8]),
+                ],
                 Volatility::Immutable,
             ),
             aliases: vec![String::from("length"), String::from("char_length")],
@@ -107,24 +120,124 @@ impl ScalarUDFImpl for CharacterLengthFunc {
 /// Returns number of characters in the string.
 /// character_length('josé') = 4
 /// The implementation counts UTF-8 code points to count the number of characters
+///
+/// When called with a second `encoding` argument, the first argument is
+/// instead treated as raw bytes in the named server encoding.
 fn character_length(args: &[ArrayRef]) -> Result<ArrayRef> {
-    match args[0].data_type() {
-        DataType::Utf8 => {
-            let string_array = args[0].as_string::<i32>();
-            character_length_general::<Int32Type, _>(string_array)
+    match args.len() {
+        1 => match args[0].data_type() {
+            DataType::Utf8 => {
+                let string_array = args[0].as_string::<i32>();
+                character_length_general::<Int32Type, _>(string_array)
+            }
+            DataType::LargeUtf8 => {
+                let string_array = args[0].as_string::<i64>();
+                character_length_general::<Int64Type, _>(string_array)
+            }
+            DataType::Utf8View => {
+                let string_array = args[0].as_string_view();
+                character_length_general::<Int32Type, _>(string_array)
+            }
+            _ => unreachable!("CharacterLengthFunc"),
+        },
+        2 => {
+            let encoding_array = args[1].as_string::<i32>();
+            match args[0].data_type() {
+                DataType::Binary => {
+                    let binary_array = args[0].as_binary::<i32>();
+                    character_length_with_encoding::<Int32Type, _>(
+                        binary_array,
+                        encoding_array,
+                    )
+                }
+                DataType::LargeBinary => {
+                    let binary_array = args[0].as_binary::<i64>();
+                    character_length_with_encoding::<Int64Type, _>(
+                        binary_array,
+                        encoding_array,
+                    )
+                }
+                other => exec_err!(
+                    "character_length does not support the binary type {other}"
+                ),
+            }
         }
-        DataType::LargeUtf8 => {
-            let string_array = args[0].as_string::<i64>();
-            character_length_general::<Int64Type, _>(string_array)
+        other => exec_err!("character_length expects 1 or 2 arguments, got {other}"),
+    }
+}
+
+/// The server encodings `length(bytea, encoding)` accepts. Only single-byte
+/// encodings and UTF-8 are supported for now; add entries here as more are
+/// requested.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharacterEncoding {
+    Utf8,
+    /// ISO-8859-1: one byte is always exactly one character.
+    Latin1,
+    /// Windows-1252: one byte is always exactly one character.
+    Win1252,
+}
+
+impl CharacterEncoding {
+    fn try_from_name(name: &str) -> Result<Self> {
+        match name.to_ascii_uppercase().as_str() {
+            "UTF8" | "UTF-8" => Ok(Self::Utf8),
+            "LATIN1" | "LATIN-1" | "ISO-8859-1" | "ISO88591" => Ok(Self::Latin1),
+            "WIN1252" | "WINDOWS-1252" => Ok(Self::Win1252),
+            other => plan_err!(
+                "unsupported encoding '{other}' for character_length; \
+                 supported encodings are UTF8, LATIN1 and WIN1252"
+            ),
         }
-        DataType::Utf8View => {
-            let string_array = args[0].as_string_view();
-            character_length_general::<Int32Type, _>(string_array)
+    }
+
+    /// Number of characters represented by `bytes` once decoded in this encoding.
+    fn char_count(&self, bytes: &[u8]) -> Result<usize> {
+        match self {
+            // UTF-8 still needs the ascii fast path: for ASCII-only input the
+            // byte length already equals the character count.
+            Self::Utf8 => {
+                if bytes.is_ascii() {
+                    Ok(bytes.len())
+                } else {
+                    let decoded = std::str::from_utf8(bytes).map_err(|e| {
+                        datafusion_common::DataFusionError::Execution(format!(
+                            "invalid UTF8 byte sequence passed to character_length: {e}"
+                        ))
+                    })?;
+                    Ok(decoded.chars().count())
+                }
+            }
+            // Single-byte encodings: every byte decodes to exactly one
+            // character, so the count is just the byte length.
+            Self::Latin1 | Self::Win1252 => Ok(bytes.len()),
         }
-        _ => unreachable!("CharacterLengthFunc"),
     }
 }
 
+fn character_length_with_encoding<T, O>(
+    binary_array: &GenericBinaryArray<O>,
+    encoding_array: &GenericStringArray<i32>,
+) -> Result<ArrayRef>
+where
+    T: ArrowPrimitiveType,
+    T::Native: OffsetSizeTrait,
+    O: OffsetSizeTrait,
+{
+    let nulls = NullBuffer::union(binary_array.nulls(), encoding_array.nulls());
+    let mut values = Vec::with_capacity(binary_array.len());
+    for i in 0..binary_array.len() {
+        if binary_array.is_null(i) || encoding_array.is_null(i) {
+            values.push(T::default_value());
+            continue;
+        }
+        let encoding = CharacterEncoding::try_from_name(encoding_array.value(i))?;
+        let count = encoding.char_count(binary_array.value(i))?;
+        values.push(T::Native::usize_as(count));
+    }
+    Ok(Arc::new(PrimitiveArray::<T>::new(values.into(), nulls)))
+}
+
 fn character_length_general<'a, T, V>(array: V) -> Result<ArrayRef>
 where
     T: ArrowPrimitiveType,
@@ -257,4 +370,64 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_character_length_with_encoding() -> Result<()> {
+        #[cfg(feature = "unicode_expressions")]
+        {
+            // "café" decoded from LATIN1 bytes is 4 characters, one byte each.
+            test_function!(
+                CharacterLengthFunc::new(),
+                vec![
+                    ColumnarValue::Scalar(ScalarValue::Binary(Some(
+                        vec![b'c', b'a', b'f', 0xE9]
+                    ))),
+                    ColumnarValue::Scalar(ScalarValue::Utf8(Some(String::from(
+                        "LATIN1"
+                    )))),
+                ],
+                Ok(Some(4)),
+                i32,
+                Int32,
+                Int32Array
+            );
+
+            test_function!(
+                CharacterLengthFunc::new(),
+                vec![
+                    ColumnarValue::Scalar(ScalarValue::Binary(Some(
+                        "chars".as_bytes().to_vec()
+                    ))),
+                    ColumnarValue::Scalar(ScalarValue::Utf8(Some(String::from(
+                        "UTF8"
+                    )))),
+                ],
+                Ok(Some(5)),
+                i32,
+                Int32,
+                Int32Array
+            );
+
+            test_function!(
+                CharacterLengthFunc::new(),
+                vec![
+                    ColumnarValue::Scalar(ScalarValue::Binary(Some(
+                        "chars".as_bytes().to_vec()
+                    ))),
+                    ColumnarValue::Scalar(ScalarValue::Utf8(Some(String::from(
+                        "SHIFT_JIS"
+                    )))),
+                ],
+                datafusion_common::plan_err!(
+                    "unsupported encoding 'SHIFT_JIS' for character_length; \
+                     supported encodings are UTF8, LATIN1 and WIN1252"
+                ),
+                i32,
+                Int32,
+                Int32Array
+            );
+        }
+
+        Ok(())
+    }
 }
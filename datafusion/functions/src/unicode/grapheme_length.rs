@@ -0,0 +1,241 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use crate::utils::{make_scalar_function, utf8_to_int_type};
+use arrow::array::{
+    Array, ArrayRef, ArrowPrimitiveType, AsArray, OffsetSizeTrait, PrimitiveArray,
+    StringArrayType,
+};
+use arrow::datatypes::{ArrowNativeType, DataType, Int32Type, Int64Type};
+use datafusion_common::Result;
+use datafusion_expr::{
+    ColumnarValue, Documentation, ScalarUDFImpl, Signature, Volatility,
+};
+use datafusion_macros::user_doc;
+use std::any::Any;
+use std::sync::Arc;
+use unicode_segmentation::UnicodeSegmentation;
+
+#[user_doc(
+    doc_section(label = "String Functions"),
+    description = "Returns the number of user-perceived characters (extended grapheme clusters, per UAX #29) in a string. Unlike `character_length`, a base character combined with its combining marks, or an emoji with modifiers, counts as a single grapheme.",
+    syntax_example = "grapheme_length(str)",
+    sql_example = r#"```sql
+> select grapheme_length('è');
++------------------------------------------------------+
+| grapheme_length(Utf8("è")) |
++------------------------------------------------------+
+| 1                                                      |
++------------------------------------------------------+
+```"#,
+    standard_argument(name = "str", prefix = "String"),
+    related_udf(name = "character_length")
+)]
+#[derive(Debug)]
+pub struct GraphemeLengthFunc {
+    signature: Signature,
+    aliases: Vec<String>,
+}
+
+impl Default for GraphemeLengthFunc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GraphemeLengthFunc {
+    pub fn new() -> Self {
+        use DataType::*;
+        Self {
+            signature: Signature::uniform(
+                1,
+                vec![Utf8, LargeUtf8, Utf8View],
+                Volatility::Immutable,
+            ),
+            aliases: vec![],
+        }
+    }
+}
+
+impl ScalarUDFImpl for GraphemeLengthFunc {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "grapheme_length"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, arg_types: &[DataType]) -> Result<DataType> {
+        utf8_to_int_type(&arg_types[0], "grapheme_length")
+    }
+
+    fn invoke_with_args(
+        &self,
+        args: datafusion_expr::ScalarFunctionArgs,
+    ) -> Result<ColumnarValue> {
+        make_scalar_function(grapheme_length, vec![])(&args.args)
+    }
+
+    fn aliases(&self) -> &[String] {
+        &self.aliases
+    }
+
+    fn documentation(&self) -> Option<&Documentation> {
+        self.doc()
+    }
+}
+
+/// Returns number of extended grapheme clusters (user-perceived characters) in the string.
+/// grapheme_length('é') = 1, even when 'é' is encoded as e + U+0301 combining acute accent.
+fn grapheme_length(args: &[ArrayRef]) -> Result<ArrayRef> {
+    match args[0].data_type() {
+        DataType::Utf8 => {
+            let string_array = args[0].as_string::<i32>();
+            grapheme_length_general::<Int32Type, _>(string_array)
+        }
+        DataType::LargeUtf8 => {
+            let string_array = args[0].as_string::<i64>();
+            grapheme_length_general::<Int64Type, _>(string_array)
+        }
+        DataType::Utf8View => {
+            let string_array = args[0].as_string_view();
+            grapheme_length_general::<Int32Type, _>(string_array)
+        }
+        _ => unreachable!("GraphemeLengthFunc"),
+    }
+}
+
+/// Counts the extended grapheme clusters of `value`, per UAX #29.
+/// For ASCII-only input every byte is its own grapheme, so the cheap
+/// byte-length fast path used by `character_length` still applies; only
+/// non-ASCII values pay for full grapheme segmentation.
+fn grapheme_count(value: &str) -> usize {
+    if value.is_ascii() {
+        value.len()
+    } else {
+        value.graphemes(true).count()
+    }
+}
+
+fn grapheme_length_general<'a, T, V>(array: V) -> Result<ArrayRef>
+where
+    T: ArrowPrimitiveType,
+    T::Native: OffsetSizeTrait,
+    V: StringArrayType<'a>,
+{
+    let is_array_ascii_only = array.is_ascii();
+    let array = if array.null_count() == 0 {
+        if is_array_ascii_only {
+            let values: Vec<_> = (0..array.len())
+                .map(|i| T::Native::usize_as(array.value(i).len()))
+                .collect();
+            PrimitiveArray::<T>::new(values.into(), None)
+        } else {
+            let values: Vec<_> = (0..array.len())
+                .map(|i| T::Native::usize_as(grapheme_count(array.value(i))))
+                .collect();
+            PrimitiveArray::<T>::new(values.into(), None)
+        }
+    } else if is_array_ascii_only {
+        let values: Vec<_> = (0..array.len())
+            .map(|i| {
+                if array.is_null(i) {
+                    T::default_value()
+                } else {
+                    T::Native::usize_as(array.value(i).len())
+                }
+            })
+            .collect();
+        PrimitiveArray::<T>::new(values.into(), array.nulls().cloned())
+    } else {
+        let values: Vec<_> = (0..array.len())
+            .map(|i| {
+                if array.is_null(i) {
+                    T::default_value()
+                } else {
+                    T::Native::usize_as(grapheme_count(array.value(i)))
+                }
+            })
+            .collect();
+        PrimitiveArray::<T>::new(values.into(), array.nulls().cloned())
+    };
+
+    Ok(Arc::new(array))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::unicode::grapheme_length::GraphemeLengthFunc;
+    use crate::utils::test::test_function;
+    use arrow::array::{Array, Int32Array, Int64Array};
+    use arrow::datatypes::DataType::{Int32, Int64};
+    use datafusion_common::{Result, ScalarValue};
+    use datafusion_expr::{ColumnarValue, ScalarUDFImpl};
+
+    macro_rules! test_grapheme_length {
+        ($INPUT:expr, $EXPECTED:expr) => {
+            test_function!(
+                GraphemeLengthFunc::new(),
+                vec![ColumnarValue::Scalar(ScalarValue::Utf8($INPUT))],
+                $EXPECTED,
+                i32,
+                Int32,
+                Int32Array
+            );
+
+            test_function!(
+                GraphemeLengthFunc::new(),
+                vec![ColumnarValue::Scalar(ScalarValue::LargeUtf8($INPUT))],
+                $EXPECTED,
+                i64,
+                Int64,
+                Int64Array
+            );
+
+            test_function!(
+                GraphemeLengthFunc::new(),
+                vec![ColumnarValue::Scalar(ScalarValue::Utf8View($INPUT))],
+                $EXPECTED,
+                i32,
+                Int32,
+                Int32Array
+            );
+        };
+    }
+
+    #[test]
+    fn test_functions() -> Result<()> {
+        #[cfg(feature = "unicode_expressions")]
+        {
+            test_grapheme_length!(Some(String::from("chars")), Ok(Some(5)));
+            // "é" as a single precomposed code point is one grapheme ...
+            test_grapheme_length!(Some(String::from("\u{e9}")), Ok(Some(1)));
+            // ... and so is "é" spelled as e + combining acute accent, even
+            // though it is two code points and `character_length` counts 2.
+            test_grapheme_length!(Some(String::from("e\u{301}")), Ok(Some(1)));
+            test_grapheme_length!(Some(String::from("")), Ok(Some(0)));
+            test_grapheme_length!(None, Ok(None));
+        }
+
+        Ok(())
+    }
+}
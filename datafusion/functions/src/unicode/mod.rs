@@ -0,0 +1,42 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! "unicode" DataFusion functions
+
+mod character_length;
+mod grapheme_length;
+
+use datafusion_expr::ScalarUDF;
+use std::sync::Arc;
+
+make_udf_function!(character_length::CharacterLengthFunc, character_length);
+make_udf_function!(grapheme_length::GraphemeLengthFunc, grapheme_length);
+
+export_functions!((
+    character_length,
+    arg1,
+    "the number of characters in the `string`."
+),(
+    grapheme_length,
+    arg1,
+    "the number of user-perceived characters (extended grapheme clusters) in the `string`."
+));
+
+/// Returns all DataFusion functions defined in this package
+pub fn functions() -> Vec<Arc<ScalarUDF>> {
+    vec![character_length(), grapheme_length()]
+}
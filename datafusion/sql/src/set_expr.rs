@@ -16,11 +16,15 @@
 // under the License.
 
 use crate::planner::{ContextProvider, PlannerContext, SqlToRel};
+use arrow::datatypes::{DataType, DECIMAL128_MAX_PRECISION, DECIMAL256_MAX_PRECISION};
 use datafusion_common::{
-    not_impl_err, plan_err, DataFusionError, Diagnostic, Result, Span,
+    not_impl_err, plan_err, Column, DataFusionError, Diagnostic, Result, Span,
+};
+use datafusion_expr::{
+    logical_plan::builder::CteWorkTable, Expr, LogicalPlan, LogicalPlanBuilder,
 };
-use datafusion_expr::{LogicalPlan, LogicalPlanBuilder};
 use sqlparser::ast::{SetExpr, SetOperator, SetQuantifier, Spanned};
+use std::sync::Arc;
 
 impl<S: ContextProvider> SqlToRel<'_, S> {
     #[cfg_attr(feature = "recursive_protection", recursive::recursive)]
@@ -54,19 +58,15 @@ impl<S: ContextProvider> SqlToRel<'_, S> {
                         return Err(err);
                     }
                 };
-                if !(set_quantifier == SetQuantifier::ByName
-                    || set_quantifier == SetQuantifier::AllByName)
-                {
-                    self.validate_set_expr_num_of_columns(
-                        op,
-                        left_span,
-                        right_span,
-                        &left_plan,
-                        &right_plan,
-                        set_expr_span,
-                    )?;
-                }
-                self.set_operation_to_plan(op, left_plan, right_plan, set_quantifier)
+                finish_set_operation(
+                    op,
+                    set_quantifier,
+                    left_plan,
+                    right_plan,
+                    left_span,
+                    right_span,
+                    set_expr_span,
+                )
             }
             SetExpr::Query(q) => self.query_to_plan(*q, planner_context),
             _ => not_impl_err!("Query {set_expr} not implemented yet"),
@@ -83,80 +83,832 @@ impl<S: ContextProvider> SqlToRel<'_, S> {
         }
     }
 
-    fn validate_set_expr_num_of_columns(
+    /// Plans the body of a `WITH [RECURSIVE] <cte_name> AS (<body>)` clause.
+    ///
+    /// Called from [`Self::plan_cte`] for every CTE of a `WITH RECURSIVE`
+    /// query, once per `Cte` in `with.cte_tables`.
+    ///
+    /// If `body` is a `UNION [ALL]` whose recursive (right-hand) term
+    /// references `cte_name` exactly once, this builds a
+    /// [`LogicalPlan::RecursiveQuery`] instead of planning it as an ordinary
+    /// set operation. A body that is not a `UNION` is planned by
+    /// [`Self::set_expr_to_plan`], and a `UNION` whose recursive term does
+    /// not reference `cte_name` is planned as an ordinary set operation.
+    /// A recursive term that references `cte_name` more than once, or that
+    /// contains an aggregation or an outer join, is an error.
+    pub(super) fn plan_recursive_cte(
         &self,
-        op: SetOperator,
-        left_span: Option<Span>,
-        right_span: Option<Span>,
-        left_plan: &LogicalPlan,
-        right_plan: &LogicalPlan,
-        set_expr_span: Option<Span>,
-    ) -> Result<()> {
-        if left_plan.schema().fields().len() == right_plan.schema().fields().len() {
-            return Ok(());
-        }
-        let diagnostic = Diagnostic::new_error(
-            format!("{} queries have different number of columns", op),
-            set_expr_span,
-        )
-        .with_note(
-            format!("this side has {} fields", left_plan.schema().fields().len()),
+        cte_name: String,
+        body: SetExpr,
+        planner_context: &mut PlannerContext,
+    ) -> Result<LogicalPlan> {
+        let SetExpr::SetOperation {
+            op: SetOperator::Union,
+            left,
+            right,
+            set_quantifier,
+        } = &body
+        else {
+            return self.set_expr_to_plan(body, planner_context);
+        };
+        let left_span = Span::try_from_sqlparser_span(left.span());
+        let right_span = Span::try_from_sqlparser_span(right.span());
+        let body_span = Span::try_from_sqlparser_span(body.span());
+
+        // Plan the anchor (non-recursive) term first; it must not reference
+        // the CTE name, since at this point it isn't registered yet.
+        let static_plan = self.set_expr_to_plan((**left).clone(), planner_context)?;
+
+        // Register a working-table placeholder under the CTE name so that a
+        // self-reference in the recursive term resolves to it, then plan the
+        // recursive term as an ordinary query.
+        let work_table = Arc::new(CteWorkTable::new(
+            cte_name.as_str(),
+            static_plan.schema().clone(),
+        ));
+        let work_table_plan = LogicalPlanBuilder::scan(
+            cte_name.clone(),
+            datafusion_expr::logical_plan::builder::provider_as_source(work_table),
+            None,
+        )?
+        .build()?;
+        planner_context.insert_cte(cte_name.clone(), work_table_plan.clone());
+        let recursive_plan = self.set_expr_to_plan((**right).clone(), planner_context)?;
+        planner_context.remove_cte(&cte_name);
+
+        let is_distinct = !Self::is_union_all(*set_quantifier)?;
+        finish_recursive_cte(
+            cte_name,
+            static_plan,
+            work_table_plan,
+            recursive_plan,
+            is_distinct,
+            *set_quantifier,
             left_span,
-        )
-        .with_note(
-            format!(
-                "this side has {} fields",
-                right_plan.schema().fields().len()
-            ),
             right_span,
-        );
-        plan_err!("{} queries have different number of columns", op; diagnostic =diagnostic)
+            body_span,
+        )
     }
 
-    pub(super) fn set_operation_to_plan(
+    /// Plans a single CTE of a `WITH [RECURSIVE]` clause.
+    ///
+    /// Under `WITH RECURSIVE`, a CTE whose body is a `UNION [ALL]` is planned
+    /// from that body alone by [`Self::plan_recursive_cte`], which builds a
+    /// recursive query when the recursive term references the CTE and an
+    /// ordinary set operation when it does not. Every other CTE goes through
+    /// [`Self::query_to_plan`].
+    pub(super) fn plan_cte(
         &self,
-        op: SetOperator,
-        left_plan: LogicalPlan,
-        right_plan: LogicalPlan,
-        set_quantifier: SetQuantifier,
-    ) -> Result<LogicalPlan> {
-        match (op, set_quantifier) {
-            (SetOperator::Union, SetQuantifier::All) => {
-                LogicalPlanBuilder::from(left_plan)
-                    .union(right_plan)?
-                    .build()
-            }
-            (SetOperator::Union, SetQuantifier::AllByName) => {
-                LogicalPlanBuilder::from(left_plan)
-                    .union_by_name(right_plan)?
-                    .build()
-            }
-            (SetOperator::Union, SetQuantifier::Distinct | SetQuantifier::None) => {
-                LogicalPlanBuilder::from(left_plan)
-                    .union_distinct(right_plan)?
-                    .build()
-            }
-            (
-                SetOperator::Union,
-                SetQuantifier::ByName | SetQuantifier::DistinctByName,
-            ) => LogicalPlanBuilder::from(left_plan)
-                .union_by_name_distinct(right_plan)?
-                .build(),
-            (SetOperator::Intersect, SetQuantifier::All) => {
-                LogicalPlanBuilder::intersect(left_plan, right_plan, true)
-            }
-            (SetOperator::Intersect, SetQuantifier::Distinct | SetQuantifier::None) => {
-                LogicalPlanBuilder::intersect(left_plan, right_plan, false)
-            }
-            (SetOperator::Except, SetQuantifier::All) => {
-                LogicalPlanBuilder::except(left_plan, right_plan, true)
-            }
-            (SetOperator::Except, SetQuantifier::Distinct | SetQuantifier::None) => {
-                LogicalPlanBuilder::except(left_plan, right_plan, false)
+        with_recursive: bool,
+        cte: sqlparser::ast::Cte,
+        planner_context: &mut PlannerContext,
+    ) -> Result<(String, LogicalPlan)> {
+        let cte_name = cte.alias.name.value;
+        let plan = if with_recursive
+            && matches!(
+                *cte.query.body,
+                SetExpr::SetOperation {
+                    op: SetOperator::Union,
+                    ..
+                }
+            )
+        {
+            self.plan_recursive_cte(cte_name.clone(), *cte.query.body, planner_context)?
+        } else {
+            self.query_to_plan(*cte.query, planner_context)?
+        };
+        Ok((cte_name, plan))
+    }
+}
+
+/// Finishes planning a `UNION`/`INTERSECT`/`EXCEPT`, after both sides have
+/// already been planned: validates column counts, coerces column types to a
+/// common type, and builds the final `LogicalPlan`.
+///
+/// `UNION [ALL | DISTINCT] BY NAME` match columns by name rather than by
+/// position, and `LogicalPlanBuilder::union_by_name[_distinct]` already
+/// resolves and aligns (and, internally, coerces) columns by name itself,
+/// so the positional column-count check and positional column coercion
+/// below do not apply and must be skipped for those quantifiers.
+fn finish_set_operation(
+    op: SetOperator,
+    set_quantifier: SetQuantifier,
+    left_plan: LogicalPlan,
+    right_plan: LogicalPlan,
+    left_span: Option<Span>,
+    right_span: Option<Span>,
+    set_expr_span: Option<Span>,
+) -> Result<LogicalPlan> {
+    let is_by_name = matches!(
+        set_quantifier,
+        SetQuantifier::ByName | SetQuantifier::AllByName | SetQuantifier::DistinctByName
+    );
+    let (left_plan, right_plan) = if is_by_name {
+        (left_plan, right_plan)
+    } else {
+        validate_set_expr_num_of_columns(
+            op,
+            left_span,
+            right_span,
+            &left_plan,
+            &right_plan,
+            set_expr_span,
+        )?;
+        coerce_set_expr_columns(
+            op,
+            left_plan,
+            right_plan,
+            left_span,
+            right_span,
+            set_expr_span,
+        )?
+    };
+    set_operation_to_plan(op, left_plan, right_plan, set_quantifier)
+}
+
+fn validate_set_expr_num_of_columns(
+    op: SetOperator,
+    left_span: Option<Span>,
+    right_span: Option<Span>,
+    left_plan: &LogicalPlan,
+    right_plan: &LogicalPlan,
+    set_expr_span: Option<Span>,
+) -> Result<()> {
+    if left_plan.schema().fields().len() == right_plan.schema().fields().len() {
+        return Ok(());
+    }
+    let diagnostic = Diagnostic::new_error(
+        format!("{} queries have different number of columns", op),
+        set_expr_span,
+    )
+    .with_note(
+        format!("this side has {} fields", left_plan.schema().fields().len()),
+        left_span,
+    )
+    .with_note(
+        format!(
+            "this side has {} fields",
+            right_plan.schema().fields().len()
+        ),
+        right_span,
+    );
+    plan_err!("{} queries have different number of columns", op; diagnostic =diagnostic)
+}
+
+fn set_operation_to_plan(
+    op: SetOperator,
+    left_plan: LogicalPlan,
+    right_plan: LogicalPlan,
+    set_quantifier: SetQuantifier,
+) -> Result<LogicalPlan> {
+    match (op, set_quantifier) {
+        (SetOperator::Union, SetQuantifier::All) => {
+            LogicalPlanBuilder::from(left_plan)
+                .union(right_plan)?
+                .build()
+        }
+        (SetOperator::Union, SetQuantifier::AllByName) => {
+            LogicalPlanBuilder::from(left_plan)
+                .union_by_name(right_plan)?
+                .build()
+        }
+        (SetOperator::Union, SetQuantifier::Distinct | SetQuantifier::None) => {
+            LogicalPlanBuilder::from(left_plan)
+                .union_distinct(right_plan)?
+                .build()
+        }
+        (
+            SetOperator::Union,
+            SetQuantifier::ByName | SetQuantifier::DistinctByName,
+        ) => LogicalPlanBuilder::from(left_plan)
+            .union_by_name_distinct(right_plan)?
+            .build(),
+        (SetOperator::Intersect, SetQuantifier::All) => {
+            LogicalPlanBuilder::intersect(left_plan, right_plan, true)
+        }
+        (SetOperator::Intersect, SetQuantifier::Distinct | SetQuantifier::None) => {
+            LogicalPlanBuilder::intersect(left_plan, right_plan, false)
+        }
+        (SetOperator::Except, SetQuantifier::All) => {
+            LogicalPlanBuilder::except(left_plan, right_plan, true)
+        }
+        (SetOperator::Except, SetQuantifier::Distinct | SetQuantifier::None) => {
+            LogicalPlanBuilder::except(left_plan, right_plan, false)
+        }
+        (op, quantifier) => {
+            not_impl_err!("{op} {quantifier} not implemented")
+        }
+    }
+}
+
+/// Coerces the columns of `left_plan` and `right_plan` to a common type,
+/// column by column, inserting a projection with `CAST`s on whichever side
+/// needs it. The two plans must already have the same number of columns,
+/// aligned positionally (see [`validate_set_expr_num_of_columns`]) - this is
+/// NOT applicable to `UNION BY NAME`, which aligns columns by name instead.
+fn coerce_set_expr_columns(
+    op: SetOperator,
+    left_plan: LogicalPlan,
+    right_plan: LogicalPlan,
+    left_span: Option<Span>,
+    right_span: Option<Span>,
+    set_expr_span: Option<Span>,
+) -> Result<(LogicalPlan, LogicalPlan)> {
+    let left_schema = left_plan.schema();
+    let right_schema = right_plan.schema();
+
+    let mut left_exprs = Vec::with_capacity(left_schema.fields().len());
+    let mut right_exprs = Vec::with_capacity(right_schema.fields().len());
+    let mut left_needs_cast = false;
+    let mut right_needs_cast = false;
+    let mut diagnostic: Option<Diagnostic> = None;
+
+    for i in 0..left_schema.fields().len() {
+        let (left_qualifier, left_field) = left_schema.qualified_field(i);
+        let (right_qualifier, right_field) = right_schema.qualified_field(i);
+        let left_column = Expr::Column(Column::from((left_qualifier, left_field)));
+        let right_column = Expr::Column(Column::from((right_qualifier, right_field)));
+
+        match common_set_op_type(left_field.data_type(), right_field.data_type()) {
+            Some(common_type) => {
+                let left_expr = if left_field.data_type() == &common_type {
+                    left_column
+                } else {
+                    left_needs_cast = true;
+                    left_column
+                        .cast_to(&common_type, left_schema)?
+                        .alias(left_field.name())
+                };
+                let right_expr = if right_field.data_type() == &common_type {
+                    right_column
+                } else {
+                    right_needs_cast = true;
+                    right_column
+                        .cast_to(&common_type, right_schema)?
+                        .alias(right_field.name())
+                };
+                left_exprs.push(left_expr);
+                right_exprs.push(right_expr);
             }
-            (op, quantifier) => {
-                not_impl_err!("{op} {quantifier} not implemented")
+            None => {
+                let d = diagnostic.take().unwrap_or_else(|| {
+                    Diagnostic::new_error(
+                        format!("{op} queries have incompatible column types"),
+                        set_expr_span,
+                    )
+                });
+                diagnostic = Some(
+                    d.with_note(
+                        format!(
+                            "this side's column {} has type {}",
+                            left_field.name(),
+                            left_field.data_type()
+                        ),
+                        left_span,
+                    )
+                    .with_note(
+                        format!(
+                            "this side's column {} has type {}",
+                            right_field.name(),
+                            right_field.data_type()
+                        ),
+                        right_span,
+                    ),
+                );
             }
         }
     }
+
+    if let Some(diagnostic) = diagnostic {
+        return plan_err!(
+            "{op} queries have incompatible column types"; diagnostic = diagnostic
+        );
+    }
+
+    let left_plan = if left_needs_cast {
+        LogicalPlanBuilder::from(left_plan)
+            .project(left_exprs)?
+            .build()?
+    } else {
+        left_plan
+    };
+    let right_plan = if right_needs_cast {
+        LogicalPlanBuilder::from(right_plan)
+            .project(right_exprs)?
+            .build()?
+    } else {
+        right_plan
+    };
+    Ok((left_plan, right_plan))
+}
+
+/// Computes the common super-type for a pair of columns being combined by a
+/// set operation (UNION/INTERSECT/EXCEPT): integers widen to the wider
+/// integer (a signed/unsigned pair to a signed type wide enough for both)
+/// or to float, and `Decimal128`/`Decimal256` precision/scale are
+/// unified to the smallest type that loses no information from either side.
+/// Returns `None` when the two types have no common representation.
+fn common_set_op_type(left: &DataType, right: &DataType) -> Option<DataType> {
+    use DataType::*;
+
+    if left == right {
+        return Some(left.clone());
+    }
+
+    if matches!(left, Decimal128(_, _) | Decimal256(_, _))
+        || matches!(right, Decimal128(_, _) | Decimal256(_, _))
+    {
+        return common_decimal_type(left, right);
+    }
+
+    fn rank(data_type: &DataType) -> Option<i32> {
+        match data_type {
+            DataType::Int8 | DataType::UInt8 => Some(0),
+            DataType::Int16 | DataType::UInt16 => Some(1),
+            DataType::Int32 | DataType::UInt32 => Some(2),
+            DataType::Int64 | DataType::UInt64 => Some(3),
+            DataType::Float32 => Some(4),
+            DataType::Float64 => Some(5),
+            _ => None,
+        }
+    }
+
+    let is_unsigned = |t: &DataType| matches!(t, UInt8 | UInt16 | UInt32 | UInt64);
+    match (rank(left), rank(right)) {
+        (Some(l), Some(r)) if is_unsigned(left) && is_unsigned(right) => {
+            Some(match l.max(r) {
+                0 => UInt8,
+                1 => UInt16,
+                2 => UInt32,
+                _ => UInt64,
+            })
+        }
+        (Some(l), Some(r)) => {
+            // An unsigned integer paired with a signed one needs the next
+            // wider signed type to hold its full range.
+            let l = if is_unsigned(left) && r <= 3 { l + 1 } else { l };
+            let r = if is_unsigned(right) && l <= 3 { r + 1 } else { r };
+            Some(match l.max(r) {
+                0 => Int8,
+                1 => Int16,
+                2 => Int32,
+                3 => Int64,
+                4 => Float32,
+                _ => Float64,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Unifies two decimal (or integer, treated as zero-scale decimal) types
+/// into the smallest decimal type that can represent every value either
+/// side can produce: the scale is the larger of the two scales, and the
+/// precision is widened by the same amount so no integer digits are lost.
+///
+/// Prefers `Decimal128`, but produces `Decimal256` when either input already
+/// is `Decimal256` or the unified precision doesn't fit in `Decimal128`. If
+/// the unified precision doesn't fit even in `Decimal256`, the scale - not
+/// the integer part - is reduced: losing fractional digits is lossy, but
+/// losing integer digits would make the resulting `CAST` overflow for values
+/// the narrower side could already legally hold.
+fn common_decimal_type(left: &DataType, right: &DataType) -> Option<DataType> {
+    let (left_precision, left_scale) = decimal_precision_and_scale(left)?;
+    let (right_precision, right_scale) = decimal_precision_and_scale(right)?;
+
+    let scale = left_scale.max(right_scale);
+    let left_integer_digits = left_precision as i32 - left_scale as i32;
+    let right_integer_digits = right_precision as i32 - right_scale as i32;
+    let integer_digits = left_integer_digits.max(right_integer_digits);
+    let needed_precision = integer_digits + scale as i32;
+
+    let either_is_decimal256 = matches!(left, DataType::Decimal256(_, _))
+        || matches!(right, DataType::Decimal256(_, _));
+    let use_decimal256 =
+        either_is_decimal256 || needed_precision > DECIMAL128_MAX_PRECISION as i32;
+    let max_precision = if use_decimal256 {
+        DECIMAL256_MAX_PRECISION
+    } else {
+        DECIMAL128_MAX_PRECISION
+    };
+
+    let precision = needed_precision.clamp(1, max_precision as i32) as u8;
+    let scale = if needed_precision > max_precision as i32 {
+        (max_precision as i32 - integer_digits).max(0) as i8
+    } else {
+        scale
+    };
+
+    Some(if use_decimal256 {
+        DataType::Decimal256(precision, scale)
+    } else {
+        DataType::Decimal128(precision, scale)
+    })
+}
+
+fn decimal_precision_and_scale(data_type: &DataType) -> Option<(u8, i8)> {
+    match data_type {
+        DataType::Decimal128(precision, scale) => Some((*precision, *scale)),
+        DataType::Decimal256(precision, scale) => Some((*precision, *scale)),
+        DataType::Int8 | DataType::UInt8 => Some((3, 0)),
+        DataType::Int16 | DataType::UInt16 => Some((5, 0)),
+        DataType::Int32 | DataType::UInt32 => Some((10, 0)),
+        DataType::Int64 | DataType::UInt64 => Some((20, 0)),
+        _ => None,
+    }
+}
+
+/// Counts how many places in `plan` scan the recursive CTE's working table.
+fn count_work_table_references(plan: &LogicalPlan, work_table_plan: &LogicalPlan) -> usize {
+    let mut count = 0;
+    plan.apply(|p| {
+        if p == work_table_plan {
+            count += 1;
+        }
+        Ok(datafusion_common::tree_node::TreeNodeRecursion::Continue)
+    })
+    .ok();
+    count
+}
+
+/// Finishes planning the recursive term of a `WITH RECURSIVE` CTE, after the
+/// anchor (`static_plan`) and self-referencing (`recursive_plan`) terms have
+/// both already been planned, with `work_table_plan` the placeholder scan
+/// that was registered under `cte_name` while planning `recursive_plan`.
+///
+/// Falls back to planning `static_plan UNION [ALL] recursive_plan` as an
+/// ordinary set operation when `recursive_plan` turns out not to reference
+/// `work_table_plan` at all (i.e. the query wasn't actually recursive).
+#[allow(clippy::too_many_arguments)]
+fn finish_recursive_cte(
+    cte_name: String,
+    static_plan: LogicalPlan,
+    work_table_plan: LogicalPlan,
+    recursive_plan: LogicalPlan,
+    is_distinct: bool,
+    set_quantifier: SetQuantifier,
+    left_span: Option<Span>,
+    right_span: Option<Span>,
+    body_span: Option<Span>,
+) -> Result<LogicalPlan> {
+    let self_reference_count =
+        count_work_table_references(&recursive_plan, &work_table_plan);
+
+    if self_reference_count == 0 {
+        // Not actually self-referencing: plan the whole body as an ordinary
+        // (non-recursive) set operation instead.
+        return finish_set_operation(
+            SetOperator::Union,
+            set_quantifier,
+            static_plan,
+            recursive_plan,
+            left_span,
+            right_span,
+            body_span,
+        );
+    }
+    if self_reference_count > 1 {
+        return not_impl_err!(
+            "WITH RECURSIVE \"{cte_name}\" must reference itself exactly once in its recursive term, found {self_reference_count} references"
+        );
+    }
+    if recursive_plan
+        .exists(|plan| Ok(matches!(plan, LogicalPlan::Aggregate(_))))
+        .unwrap_or(false)
+    {
+        return not_impl_err!(
+            "aggregation in the recursive term of WITH RECURSIVE \"{cte_name}\" is not supported"
+        );
+    }
+    if recursive_plan
+        .exists(|plan| {
+            Ok(matches!(
+                plan,
+                LogicalPlan::Join(join) if join.join_type.is_outer()
+            ))
+        })
+        .unwrap_or(false)
+    {
+        return not_impl_err!(
+            "outer join in the recursive term of WITH RECURSIVE \"{cte_name}\" is not supported"
+        );
+    }
+
+    validate_set_expr_num_of_columns(
+        SetOperator::Union,
+        left_span,
+        right_span,
+        &static_plan,
+        &recursive_plan,
+        body_span,
+    )?;
+
+    LogicalPlanBuilder::from(static_plan)
+        .to_recursive_query(cte_name, recursive_plan, is_distinct)?
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion_common::ScalarValue;
+    use datafusion_expr::JoinType;
+
+    /// A single-row, single-column `VALUES` plan with an unqualified column
+    /// named `name` of the given type - enough to exercise planning logic
+    /// that only cares about a plan's schema, without needing a real table
+    /// or a `ContextProvider`.
+    fn values_plan(name: &str, value: ScalarValue) -> LogicalPlan {
+        LogicalPlanBuilder::values(vec![vec![Expr::Literal(value, None)]])
+            .unwrap()
+            .project(vec![Expr::Column(Column::new_unqualified("column1"))
+                .alias(name)])
+            .unwrap()
+            .build()
+            .unwrap()
+    }
+
+    fn two_column_plan(
+        a_name: &str,
+        a: ScalarValue,
+        b_name: &str,
+        b: ScalarValue,
+    ) -> LogicalPlan {
+        LogicalPlanBuilder::values(vec![vec![Expr::Literal(a, None), Expr::Literal(b, None)]])
+            .unwrap()
+            .project(vec![
+                Expr::Column(Column::new_unqualified("column1")).alias(a_name),
+                Expr::Column(Column::new_unqualified("column2")).alias(b_name),
+            ])
+            .unwrap()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn common_set_op_type_widens_integers() {
+        assert_eq!(
+            common_set_op_type(&DataType::Int32, &DataType::Int64),
+            Some(DataType::Int64)
+        );
+    }
+
+    #[test]
+    fn common_set_op_type_widens_int_to_float() {
+        assert_eq!(
+            common_set_op_type(&DataType::Int64, &DataType::Float32),
+            Some(DataType::Float32)
+        );
+    }
+
+    #[test]
+    fn common_set_op_type_rejects_incompatible_types() {
+        assert_eq!(common_set_op_type(&DataType::Utf8, &DataType::Int32), None);
+    }
+
+    #[test]
+    fn common_decimal_type_unifies_precision_and_scale() {
+        assert_eq!(
+            common_decimal_type(
+                &DataType::Decimal128(10, 2),
+                &DataType::Decimal128(8, 4)
+            ),
+            Some(DataType::Decimal128(12, 4))
+        );
+    }
+
+    #[test]
+    fn common_decimal_type_preserves_integer_digits_when_precision_is_capped() {
+        // Left needs 36 integer digits; naively capping precision at 38 and
+        // keeping the larger (right-hand) scale of 30 would leave room for
+        // only 8 integer digits, truncating values the left side could
+        // legally hold. The fix must widen to Decimal256 instead.
+        let common =
+            common_decimal_type(&DataType::Decimal128(38, 2), &DataType::Decimal128(38, 30))
+                .unwrap();
+        let (precision, scale) = decimal_precision_and_scale(&common).unwrap();
+        assert!(matches!(common, DataType::Decimal256(_, _)));
+        assert!(
+            precision as i32 - scale as i32 >= 36,
+            "common type {common:?} lost integer digits needed by the left side"
+        );
+        assert!(scale >= 30, "common type {common:?} lost the right side's scale");
+    }
+
+    #[test]
+    fn common_decimal_type_keeps_decimal128_when_it_fits() {
+        let common =
+            common_decimal_type(&DataType::Decimal128(10, 2), &DataType::Int32).unwrap();
+        assert!(matches!(common, DataType::Decimal128(_, _)));
+    }
+
+    #[test]
+    fn coerce_set_expr_columns_casts_mismatched_numeric_columns() {
+        let left = values_plan("n", ScalarValue::Int32(Some(1)));
+        let right = values_plan("n", ScalarValue::Float64(Some(1.5)));
+
+        let (left, right) =
+            coerce_set_expr_columns(SetOperator::Union, left, right, None, None, None)
+                .unwrap();
+
+        assert_eq!(left.schema().field(0).data_type(), &DataType::Float64);
+        assert_eq!(right.schema().field(0).data_type(), &DataType::Float64);
+    }
+
+    #[test]
+    fn coerce_set_expr_columns_errors_on_incompatible_types() {
+        let left = values_plan("n", ScalarValue::Int32(Some(1)));
+        let right = values_plan("n", ScalarValue::Utf8(Some("x".to_string())));
+
+        let err =
+            coerce_set_expr_columns(SetOperator::Union, left, right, None, None, None)
+                .unwrap_err();
+        assert!(err.to_string().contains("incompatible column types"));
+    }
+
+    #[test]
+    fn finish_set_operation_skips_positional_checks_for_union_by_name() {
+        // Same columns, reordered: a plain positional UNION would either
+        // reject this (different apparent "types" lined up wrong) or, worse,
+        // silently cast the wrong pairs together. BY NAME must not run the
+        // positional guard at all and let `union_by_name` align by name.
+        let left = two_column_plan(
+            "a",
+            ScalarValue::Int32(Some(1)),
+            "b",
+            ScalarValue::Utf8(Some("x".to_string())),
+        );
+        let right = two_column_plan(
+            "b",
+            ScalarValue::Utf8(Some("y".to_string())),
+            "a",
+            ScalarValue::Int32(Some(2)),
+        );
+
+        let plan = finish_set_operation(
+            SetOperator::Union,
+            SetQuantifier::AllByName,
+            left,
+            right,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(plan.schema().fields().len(), 2);
+    }
+
+    #[test]
+    fn finish_set_operation_union_by_name_allows_differing_column_counts() {
+        // `UNION BY NAME` with a differing number of columns on each side is
+        // valid SQL (missing columns on either side are filled with NULL);
+        // it must not go through the positional column-count check, which
+        // would otherwise reject it - or, prior to this fix, panic trying to
+        // coerce column `i` of one side against a nonexistent column `i` of
+        // the other.
+        let left = two_column_plan(
+            "a",
+            ScalarValue::Int32(Some(1)),
+            "b",
+            ScalarValue::Int32(Some(2)),
+        );
+        let right = values_plan("a", ScalarValue::Int32(Some(1)));
+
+        let plan = finish_set_operation(
+            SetOperator::Union,
+            SetQuantifier::AllByName,
+            left,
+            right,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(plan.schema().fields().len(), 2);
+    }
+
+    #[test]
+    fn finish_recursive_cte_falls_back_to_plain_union_without_self_reference() {
+        let static_plan = values_plan("n", ScalarValue::Int64(Some(1)));
+        let recursive_plan = values_plan("n", ScalarValue::Int64(Some(2)));
+        let work_table_plan = values_plan("n", ScalarValue::Int64(Some(3)));
+
+        let plan = finish_recursive_cte(
+            "r".to_string(),
+            static_plan,
+            work_table_plan,
+            recursive_plan,
+            false,
+            SetQuantifier::All,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert!(!matches!(plan, LogicalPlan::RecursiveQuery(_)));
+    }
+
+    #[test]
+    fn finish_recursive_cte_builds_recursive_query_on_single_self_reference() {
+        let static_plan = values_plan("n", ScalarValue::Int64(Some(1)));
+        let work_table_plan = values_plan("n", ScalarValue::Int64(Some(2)));
+        let recursive_plan = LogicalPlanBuilder::from(work_table_plan.clone())
+            .project(vec![Expr::Column(Column::new_unqualified("n"))])
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let plan = finish_recursive_cte(
+            "r".to_string(),
+            static_plan,
+            work_table_plan,
+            recursive_plan,
+            false,
+            SetQuantifier::Distinct,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert!(matches!(plan, LogicalPlan::RecursiveQuery(_)));
+    }
+
+    #[test]
+    fn finish_recursive_cte_rejects_more_than_one_self_reference() {
+        let static_plan = values_plan("n", ScalarValue::Int64(Some(1)));
+        let work_table_plan = values_plan("n", ScalarValue::Int64(Some(2)));
+        let recursive_plan = LogicalPlanBuilder::from(work_table_plan.clone())
+            .union(work_table_plan.clone())
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let err = finish_recursive_cte(
+            "r".to_string(),
+            static_plan,
+            work_table_plan,
+            recursive_plan,
+            false,
+            SetQuantifier::All,
+            None,
+            None,
+            None,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("exactly once"));
+    }
+
+    #[test]
+    fn finish_recursive_cte_rejects_aggregation_in_recursive_term() {
+        let static_plan = values_plan("n", ScalarValue::Int64(Some(1)));
+        let work_table_plan = values_plan("n", ScalarValue::Int64(Some(2)));
+        let recursive_plan = LogicalPlanBuilder::from(work_table_plan.clone())
+            .aggregate(
+                vec![Expr::Column(Column::new_unqualified("n"))],
+                Vec::<Expr>::new(),
+            )
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let err = finish_recursive_cte(
+            "r".to_string(),
+            static_plan,
+            work_table_plan,
+            recursive_plan,
+            false,
+            SetQuantifier::All,
+            None,
+            None,
+            None,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("aggregation"));
+    }
+
+    #[test]
+    fn finish_recursive_cte_rejects_outer_join_in_recursive_term() {
+        let static_plan = values_plan("n", ScalarValue::Int64(Some(1)));
+        let work_table_plan = values_plan("n", ScalarValue::Int64(Some(2)));
+        let other = values_plan("m", ScalarValue::Int64(Some(2)));
+        let recursive_plan = LogicalPlanBuilder::from(work_table_plan.clone())
+            .join_on(other, JoinType::Left, vec![])
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let err = finish_recursive_cte(
+            "r".to_string(),
+            static_plan,
+            work_table_plan,
+            recursive_plan,
+            false,
+            SetQuantifier::All,
+            None,
+            None,
+            None,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("outer join"));
+    }
 }
@@ -0,0 +1,97 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Lifts a Substrait [`Plan`] back into a DataFusion [`LogicalPlan`].
+
+use super::set_ops::set_rel_to_logical_plan;
+use datafusion::prelude::SessionContext;
+use datafusion_common::{not_impl_err, substrait_err, Column, Result};
+use datafusion_expr::{Expr, LogicalPlan, LogicalPlanBuilder};
+use futures::future::{BoxFuture, FutureExt};
+use substrait::proto::{plan_rel, rel::RelType, Plan, ReadRel};
+
+/// Converts a Substrait [`Plan`] into a DataFusion [`LogicalPlan`], starting
+/// from its root relation.
+pub async fn from_substrait_plan(ctx: &SessionContext, plan: &Plan) -> Result<LogicalPlan> {
+    let Some(plan_rel) = plan.relations.first() else {
+        return substrait_err!("Substrait plan must have at least one relation");
+    };
+    let rel = match &plan_rel.rel_type {
+        Some(plan_rel::RelType::Root(root)) => root
+            .input
+            .as_ref()
+            .ok_or_else(|| datafusion_common::DataFusionError::Substrait(
+                "Substrait RelRoot must have an input".to_string(),
+            ))?,
+        Some(plan_rel::RelType::Rel(rel)) => rel,
+        None => return substrait_err!("Substrait PlanRel must have a rel_type"),
+    };
+
+    from_substrait_rel(ctx, rel).await
+}
+
+/// Converts a Substrait [`substrait::proto::Rel`] into a DataFusion
+/// [`LogicalPlan`].
+///
+/// Mirrors [`super::producer::to_substrait_rel`]: only `Read`/`NamedTable`
+/// and `Set` relations are handled, since those are the only ones the
+/// producer in this crate currently emits.
+pub fn from_substrait_rel<'a>(
+    ctx: &'a SessionContext,
+    rel: &'a substrait::proto::Rel,
+) -> BoxFuture<'a, Result<LogicalPlan>> {
+    async move {
+        match &rel.rel_type {
+            Some(RelType::Read(read)) => from_substrait_read_rel(ctx, read).await,
+            Some(RelType::Set(set_rel)) => {
+                let mut inputs = Vec::with_capacity(set_rel.inputs.len());
+                for input in &set_rel.inputs {
+                    inputs.push(from_substrait_rel(ctx, input).await?);
+                }
+                set_rel_to_logical_plan(set_rel, inputs)
+            }
+            other => not_impl_err!("Substrait consumer: rel_type {other:?} is not supported yet"),
+        }
+    }
+    .boxed()
+}
+
+async fn from_substrait_read_rel(ctx: &SessionContext, read: &ReadRel) -> Result<LogicalPlan> {
+    let Some(substrait::proto::read_rel::ReadType::NamedTable(named_table)) = &read.read_type
+    else {
+        return not_impl_err!("Substrait consumer: ReadRel without a NamedTable is not supported yet");
+    };
+    let Some(table_name) = named_table.names.first() else {
+        return substrait_err!("Substrait NamedTable must have at least one name");
+    };
+
+    let plan = ctx.table(table_name).await?.into_unoptimized_plan();
+
+    let Some(base_schema) = &read.base_schema else {
+        return Ok(plan);
+    };
+    if base_schema.names.is_empty() {
+        return Ok(plan);
+    }
+
+    let exprs = base_schema
+        .names
+        .iter()
+        .map(|name| Expr::Column(Column::new_unqualified(name)))
+        .collect::<Vec<_>>();
+    LogicalPlanBuilder::from(plan).project(exprs)?.build()
+}
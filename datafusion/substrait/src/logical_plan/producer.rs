@@ -0,0 +1,202 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Lowers a DataFusion [`LogicalPlan`] into a Substrait [`Plan`].
+
+use super::set_ops::{reorder_union_by_name_inputs, union_to_set_rel};
+use datafusion_common::{not_impl_err, Result};
+use datafusion_expr::{Distinct, LogicalPlan};
+use substrait::proto::{
+    extensions::SimpleExtensionDeclaration,
+    plan_rel, r#type,
+    read_rel::{NamedTable, ReadType},
+    rel, rel::RelType, NamedStruct, Plan, PlanRel, ReadRel, Rel, RelRoot, Type, Version,
+};
+
+/// Converts a DataFusion [`LogicalPlan`] into a Substrait [`Plan`] with a
+/// single `PlanRel::Root` wrapping the result of [`to_substrait_rel`].
+pub fn to_substrait_plan(plan: &LogicalPlan) -> Result<Box<Plan>> {
+    let root_names = plan.schema().columns().into_iter().map(|c| c.name).collect();
+    let root = Rel {
+        rel_type: Some(to_substrait_rel(plan)?),
+    };
+
+    Ok(Box::new(Plan {
+        version: Some(Version {
+            major_number: 0,
+            minor_number: 54,
+            patch_number: 0,
+            git_hash: String::new(),
+            producer: "datafusion".to_string(),
+        }),
+        extension_uris: vec![],
+        extensions: Vec::<SimpleExtensionDeclaration>::new(),
+        relations: vec![PlanRel {
+            rel_type: Some(plan_rel::RelType::Root(RelRoot {
+                input: Some(root),
+                names: root_names,
+            })),
+        }],
+        advanced_extensions: None,
+        expected_type_urls: vec![],
+    }))
+}
+
+/// Converts a DataFusion [`LogicalPlan`] into a Substrait [`rel::RelType`].
+///
+/// Only a base-table scan (without filters or a fetch limit), a `Union`,
+/// and a `Distinct` directly over a `Union` are handled here; any other
+/// node, a `Projection` included, returns a not-implemented error. Every
+/// `Union`'s inputs are reordered by name with
+/// [`reorder_union_by_name_inputs`]. `INTERSECT`/`EXCEPT` also lower to a
+/// Substrait `SetRel` (see [`crate::logical_plan::set_ops`]), but
+/// `LogicalPlanBuilder::intersect`/`except` build those on top of a join
+/// today rather than a plain `LogicalPlan::Distinct(Distinct::All(..))`
+/// wrapping two inputs the way a `UNION DISTINCT` does, so recognizing them
+/// here would mean matching a join shape that isn't yet pinned down against
+/// this crate's other planning code - left as a follow-up rather than
+/// guessed at.
+pub fn to_substrait_rel(plan: &LogicalPlan) -> Result<rel::RelType> {
+    match plan {
+        LogicalPlan::TableScan(scan) => {
+            if !scan.filters.is_empty() {
+                return not_impl_err!(
+                    "Substrait producer: TableScan with filters is not supported yet"
+                );
+            }
+            if scan.fetch.is_some() {
+                return not_impl_err!(
+                    "Substrait producer: TableScan with a fetch limit is not supported yet"
+                );
+            }
+
+            let names: Vec<String> = scan
+                .projected_schema
+                .columns()
+                .into_iter()
+                .map(|c| c.name)
+                .collect();
+            // Real Arrow -> Substrait type mapping is out of scope here: only
+            // `NamedStruct.names` carries real information, one placeholder
+            // `i64` type per column keeps `r#struct.types` the right length
+            // without claiming to encode the column's actual data type.
+            let types = names
+                .iter()
+                .map(|_| Type {
+                    kind: Some(r#type::Kind::I64(r#type::I64 {
+                        type_variation_reference: 0,
+                        nullability: r#type::Nullability::Nullable as i32,
+                    })),
+                })
+                .collect();
+
+            Ok(RelType::Read(Box::new(ReadRel {
+                common: None,
+                base_schema: Some(NamedStruct {
+                    names,
+                    r#struct: Some(r#type::Struct {
+                        types,
+                        type_variation_reference: 0,
+                        nullability: r#type::Nullability::Nullable as i32,
+                    }),
+                }),
+                filter: None,
+                best_effort_filter: None,
+                projection: None,
+                advanced_extension: None,
+                read_type: Some(ReadType::NamedTable(NamedTable {
+                    names: vec![scan.table_name.to_string()],
+                    advanced_extension: None,
+                })),
+            })))
+        }
+        LogicalPlan::Union(union) => {
+            let inputs = reorder_union_by_name_inputs(union.inputs.iter().map(|i| i.as_ref().clone()).collect())?;
+            let input_rels = inputs
+                .iter()
+                .map(|input| {
+                    Ok(Rel {
+                        rel_type: Some(to_substrait_rel(input)?),
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(RelType::Set(union_to_set_rel(&input_rels, true)?))
+        }
+        LogicalPlan::Distinct(Distinct::All(input)) => match input.as_ref() {
+            LogicalPlan::Union(union) => {
+                let inputs = reorder_union_by_name_inputs(
+                    union.inputs.iter().map(|i| i.as_ref().clone()).collect(),
+                )?;
+                let input_rels = inputs
+                    .iter()
+                    .map(|input| {
+                        Ok(Rel {
+                            rel_type: Some(to_substrait_rel(input)?),
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(RelType::Set(union_to_set_rel(&input_rels, false)?))
+            }
+            other => not_impl_err!(
+                "Substrait producer: DISTINCT over {} is not supported yet",
+                other.display()
+            ),
+        },
+        other => not_impl_err!(
+            "Substrait producer: LogicalPlan {} is not supported yet",
+            other.display()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logical_plan::consumer::from_substrait_plan;
+    use datafusion::prelude::SessionContext;
+    use datafusion_common::Result;
+
+    async fn roundtrip(sql: &str) -> Result<()> {
+        let ctx = SessionContext::new();
+        ctx.sql("create table t1 (a int, b int) as values (1, 2)")
+            .await?;
+        ctx.sql("create table t2 (a int, b int) as values (3, 4)")
+            .await?;
+
+        let plan = ctx.sql(sql).await?.into_unoptimized_plan();
+        let substrait_plan = to_substrait_plan(&plan)?;
+        let round_tripped = from_substrait_plan(&ctx, &substrait_plan).await?;
+
+        assert_eq!(format!("{plan}"), format!("{round_tripped}"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn union_all_roundtrips() -> Result<()> {
+        roundtrip("SELECT a, b FROM t1 UNION ALL SELECT a, b FROM t2").await
+    }
+
+    #[tokio::test]
+    async fn union_distinct_roundtrips() -> Result<()> {
+        roundtrip("SELECT a, b FROM t1 UNION SELECT a, b FROM t2").await
+    }
+
+    #[tokio::test]
+    async fn union_by_name_roundtrips() -> Result<()> {
+        roundtrip("SELECT a, b FROM t1 UNION ALL BY NAME SELECT b, a FROM t2").await
+    }
+}
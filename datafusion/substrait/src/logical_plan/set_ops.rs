@@ -0,0 +1,247 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Substrait `SetRel` support for `Union`/`Intersect`/`Except` logical plans.
+//!
+//! Substrait's `SetRel` is purely positional, so a `UNION BY NAME` cannot be
+//! represented directly: [`reorder_union_by_name_inputs`] must be called on
+//! the logical-plan inputs of a `LogicalPlan::Union` coming from `UNION [ALL]
+//! BY NAME` *before* each input is lowered to a `Rel`, to reorder every
+//! input's columns to match the first input's column order. Once that is
+//! done, [`union_to_set_rel`] / [`intersect_or_except_to_set_rel`] produce a
+//! plain positional `SetRel` that any standard Substrait consumer can
+//! interpret without needing to know BY NAME ever happened.
+//!
+//! [`super::producer::to_substrait_rel`] calls [`reorder_union_by_name_inputs`]
+//! (for `Union` only) followed by [`union_to_set_rel`] for a
+//! `LogicalPlan::Union`/`LogicalPlan::Distinct(Distinct::All(Union))`, and
+//! [`super::consumer::from_substrait_rel`] calls [`set_rel_to_logical_plan`]
+//! for a `RelType::Set(set_rel)` - which already decodes `INTERSECT`/`EXCEPT`
+//! `SetRel`s on the way back in. [`intersect_or_except_to_set_rel`] is the
+//! other half of that round trip, but nothing in `to_substrait_rel` calls it
+//! yet: see the doc comment there for why `LogicalPlan::Intersect`/`Except`
+//! recognition was left out of this pass.
+
+use datafusion_common::{not_impl_err, plan_err, Column, Result};
+use datafusion_expr::{Expr, LogicalPlan, LogicalPlanBuilder};
+use substrait::proto::{set_rel, Rel, SetRel};
+
+/// Reorders the columns of every input after the first to match the first
+/// input's column order, by name. This is how a `UNION [ALL] BY NAME` is
+/// lowered to a plain positional union before being handed to
+/// [`union_to_set_rel`]: once every input's columns line up positionally by
+/// name, an ordinary positional `SetRel` already has the right semantics, so
+/// no Substrait-level BY NAME marker is needed at all.
+pub fn reorder_union_by_name_inputs(inputs: Vec<LogicalPlan>) -> Result<Vec<LogicalPlan>> {
+    let mut inputs = inputs.into_iter();
+    let Some(first) = inputs.next() else {
+        return Ok(Vec::new());
+    };
+    let column_order: Vec<Column> = first
+        .schema()
+        .columns()
+        .into_iter()
+        .map(|c| Column::new_unqualified(c.name))
+        .collect();
+
+    let mut reordered = vec![first];
+    for input in inputs {
+        let exprs = column_order
+            .iter()
+            .map(|column| -> Result<Expr> {
+                if input.schema().field_from_column(column).is_err() {
+                    return plan_err!(
+                        "UNION BY NAME: column \"{}\" is missing from one side",
+                        column.name
+                    );
+                }
+                Ok(Expr::Column(column.clone()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        reordered.push(LogicalPlanBuilder::from(input).project(exprs)?.build()?);
+    }
+    Ok(reordered)
+}
+
+/// Lowers a (logical) `UNION [ALL]` into a Substrait `SetRel`.
+///
+/// `inputs` must already be positionally aligned - for `UNION BY NAME`, that
+/// means each one has already gone through [`reorder_union_by_name_inputs`]
+/// (at the logical-plan level, before being lowered to a `Rel`) by the
+/// caller.
+pub fn union_to_set_rel(inputs: &[Rel], all: bool) -> Result<Box<SetRel>> {
+    if inputs.len() < 2 {
+        return plan_err!("a Substrait SetRel for UNION needs at least two inputs");
+    }
+    let op = if all {
+        set_rel::SetOp::UnionAll
+    } else {
+        set_rel::SetOp::UnionDistinct
+    };
+    Ok(Box::new(SetRel {
+        common: None,
+        inputs: inputs.to_vec(),
+        op: op as i32,
+        advanced_extension: None,
+    }))
+}
+
+/// Lowers a (logical) `INTERSECT`/`EXCEPT` `ALL`/`DISTINCT` into a Substrait
+/// `SetRel`.
+pub fn intersect_or_except_to_set_rel(
+    left: Rel,
+    right: Rel,
+    is_intersect: bool,
+    all: bool,
+) -> Result<Box<SetRel>> {
+    let op = match (is_intersect, all) {
+        (true, true) => set_rel::SetOp::IntersectionMultiset,
+        (true, false) => set_rel::SetOp::IntersectionPrimary,
+        (false, true) => set_rel::SetOp::MinusMultiset,
+        (false, false) => set_rel::SetOp::MinusPrimary,
+    };
+    Ok(Box::new(SetRel {
+        common: None,
+        inputs: vec![left, right],
+        op: op as i32,
+        advanced_extension: None,
+    }))
+}
+
+/// Reconstructs the `LogicalPlanBuilder` call that produced a `SetRel`,
+/// turning a decoded Substrait set relation back into a `Union`/`Intersect`/
+/// `Except` logical plan.
+///
+/// `inputs` are the already-consumed child plans, in the same order as
+/// `set_rel.inputs`. A `SetRel` is always positional (see
+/// [`reorder_union_by_name_inputs`]), so this always builds a plain
+/// positional union/intersect/except - there is no BY NAME case to handle
+/// here.
+pub fn set_rel_to_logical_plan(
+    set_rel: &SetRel,
+    inputs: Vec<LogicalPlan>,
+) -> Result<LogicalPlan> {
+    let op = set_rel::SetOp::try_from(set_rel.op).map_err(|_| {
+        datafusion_common::DataFusionError::Substrait(format!(
+            "invalid SetRel.op value {}",
+            set_rel.op
+        ))
+    })?;
+
+    match op {
+        set_rel::SetOp::UnionAll | set_rel::SetOp::UnionDistinct => {
+            let mut inputs = inputs.into_iter();
+            let Some(first) = inputs.next() else {
+                return plan_err!("SetRel for UNION must have at least one input");
+            };
+            let mut builder = LogicalPlanBuilder::from(first);
+            for input in inputs {
+                builder = match op {
+                    set_rel::SetOp::UnionAll => builder.union(input)?,
+                    set_rel::SetOp::UnionDistinct => builder.union_distinct(input)?,
+                    _ => unreachable!(),
+                };
+            }
+            builder.build()
+        }
+        set_rel::SetOp::IntersectionPrimary | set_rel::SetOp::IntersectionMultiset => {
+            let [left, right]: [LogicalPlan; 2] = inputs
+                .try_into()
+                .map_err(|_| plan_err_wrong_arity("INTERSECT"))?;
+            LogicalPlanBuilder::intersect(
+                left,
+                right,
+                op == set_rel::SetOp::IntersectionMultiset,
+            )
+        }
+        set_rel::SetOp::MinusPrimary | set_rel::SetOp::MinusMultiset => {
+            let [left, right]: [LogicalPlan; 2] = inputs
+                .try_into()
+                .map_err(|_| plan_err_wrong_arity("EXCEPT"))?;
+            LogicalPlanBuilder::except(left, right, op == set_rel::SetOp::MinusMultiset)
+        }
+        other => not_impl_err!("Unsupported Substrait SetRel.op {other:?}"),
+    }
+}
+
+fn plan_err_wrong_arity(op: &str) -> datafusion_common::DataFusionError {
+    datafusion_common::DataFusionError::Plan(format!(
+        "Substrait SetRel for {op} must have exactly two inputs"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion_common::ScalarValue;
+
+    fn two_column_plan(a_name: &str, b_name: &str) -> LogicalPlan {
+        LogicalPlanBuilder::values(vec![vec![
+            Expr::Literal(ScalarValue::Int32(Some(1)), None),
+            Expr::Literal(ScalarValue::Int32(Some(2)), None),
+        ]])
+        .unwrap()
+        .project(vec![
+            Expr::Column(Column::new_unqualified("column1")).alias(a_name),
+            Expr::Column(Column::new_unqualified("column2")).alias(b_name),
+        ])
+        .unwrap()
+        .build()
+        .unwrap()
+    }
+
+    #[test]
+    fn reorder_union_by_name_inputs_reorders_to_match_first_input() {
+        let left = two_column_plan("a", "b");
+        let right = two_column_plan("b", "a");
+
+        let reordered = reorder_union_by_name_inputs(vec![left, right]).unwrap();
+        let names: Vec<_> = reordered[1]
+            .schema()
+            .columns()
+            .into_iter()
+            .map(|c| c.name)
+            .collect();
+        assert_eq!(names, vec!["a".to_string(), "b".to_string()]);
+    }
+
+    #[test]
+    fn reorder_union_by_name_inputs_errors_on_missing_column() {
+        let left = two_column_plan("a", "b");
+        let right = two_column_plan("a", "c");
+
+        let err = reorder_union_by_name_inputs(vec![left, right]).unwrap_err();
+        assert!(err.to_string().contains("missing"));
+    }
+
+    #[test]
+    fn union_to_set_rel_requires_at_least_two_inputs() {
+        let err = union_to_set_rel(&[], true).unwrap_err();
+        assert!(err.to_string().contains("at least two inputs"));
+    }
+
+    #[test]
+    fn set_rel_to_logical_plan_rejects_invalid_op() {
+        let set_rel = SetRel {
+            common: None,
+            inputs: vec![],
+            op: 9999,
+            advanced_extension: None,
+        };
+        assert!(set_rel_to_logical_plan(&set_rel, vec![]).is_err());
+    }
+}